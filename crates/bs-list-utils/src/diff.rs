@@ -1,6 +1,8 @@
 use crate::HasItemKey;
-use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, TryReserveError};
 use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
 use std::ops::Deref;
 
 #[derive(Debug)]
@@ -32,6 +34,39 @@ where
   }
 }
 
+/// Result of [`Diff::with_changes`]: `both` split by whether the paired
+/// values actually differ.
+#[derive(Debug)]
+pub struct Changes<'a, Left, Right> {
+  pub unchanged: Vec<(&'a Left, &'a Right)>,
+  pub changed: Vec<(&'a Left, &'a Right)>,
+}
+
+impl<'a, Left, Right> Diff<'a, Left, Right> {
+  /// Splits `both` into `unchanged` and `changed` pairs using `eq` to compare
+  /// the paired values, turning the key-based set diff into an
+  /// added/removed/modified/unchanged record diff.
+  pub fn with_changes<F>(&self, eq: F) -> Changes<'a, Left, Right>
+  where
+    F: Fn(&Left, &Right) -> bool,
+  {
+    let mut changes = Changes {
+      unchanged: vec![],
+      changed: vec![],
+    };
+
+    for &(l, r) in &self.both {
+      if eq(l, r) {
+        changes.unchanged.push((l, r));
+      } else {
+        changes.changed.push((l, r));
+      }
+    }
+
+    changes
+  }
+}
+
 pub fn diff<'a, 'k, K, Left, Right>(left: &'a [Left], right: &'a [Right]) -> Diff<'a, Left, Right>
 where
   'a: 'k,
@@ -39,22 +74,205 @@ where
   Left: HasItemKey<'k, K>,
   Right: HasItemKey<'k, K>,
 {
-  let mut ignored = vec![];
-  let mut left_map: BTreeMap<K, &Left> = BTreeMap::new();
-  let mut right_map: BTreeMap<K, &Right> = BTreeMap::new();
+  try_diff(left, right).expect("allocation failed while diffing")
+}
 
+/// Like [`diff`], but uses fallible allocation for every `Vec` it builds, so
+/// oversized inputs are rejected with an error instead of aborting the process.
+/// The scratch buffer of the stable sort is allocated infallibly.
+///
+/// `BTreeMap` has no fallible-insert API in `std`, so this builds on the same
+/// sort-then-merge-join approach as [`diff_by`], keeping every allocation a
+/// `Vec` we can `try_reserve`.
+pub fn try_diff<'a, 'k, K, Left, Right>(
+  left: &'a [Left],
+  right: &'a [Right],
+) -> Result<Diff<'a, Left, Right>, TryReserveError>
+where
+  'a: 'k,
+  K: Ord,
+  Left: HasItemKey<'k, K>,
+  Right: HasItemKey<'k, K>,
+{
+  let mut left_keyed: Vec<(K, &Left)> = Vec::new();
+  left_keyed.try_reserve(left.len())?;
   for item in left {
-    if let Some(replaced) = left_map.insert(item.get_item_key(), item) {
-      ignored.push(DiffIgnored::Left(replaced));
-    }
+    left_keyed.push((item.get_item_key(), item));
   }
 
+  let mut right_keyed: Vec<(K, &Right)> = Vec::new();
+  right_keyed.try_reserve(right.len())?;
   for item in right {
-    if let Some(replaced) = right_map.insert(item.get_item_key(), item) {
-      ignored.push(DiffIgnored::Right(replaced));
+    right_keyed.push((item.get_item_key(), item));
+  }
+
+  let cmp = |a: &K, b: &K| a.cmp(b);
+  let (left_sorted, left_ignored) = try_sort_dedup_by(left_keyed, cmp)?;
+  let (right_sorted, right_ignored) = try_sort_dedup_by(right_keyed, cmp)?;
+
+  let mut ignored = Vec::new();
+  ignored.try_reserve(left_ignored.len() + right_ignored.len())?;
+  ignored.extend(left_ignored.into_iter().map(DiffIgnored::Left));
+  ignored.extend(right_ignored.into_iter().map(DiffIgnored::Right));
+
+  let mut left_out = Vec::new();
+  let mut both_out = Vec::new();
+  let mut right_out = Vec::new();
+  left_out.try_reserve(left_sorted.len())?;
+  both_out.try_reserve(left_sorted.len().min(right_sorted.len()))?;
+  right_out.try_reserve(right_sorted.len())?;
+
+  let mut li = 0;
+  let mut ri = 0;
+
+  while li < left_sorted.len() && ri < right_sorted.len() {
+    match cmp(&left_sorted[li].0, &right_sorted[ri].0) {
+      Ordering::Less => {
+        left_out.push(left_sorted[li].1);
+        li += 1;
+      }
+      Ordering::Greater => {
+        right_out.push(right_sorted[ri].1);
+        ri += 1;
+      }
+      Ordering::Equal => {
+        both_out.push((left_sorted[li].1, right_sorted[ri].1));
+        li += 1;
+        ri += 1;
+      }
+    }
+  }
+
+  left_out.extend(left_sorted[li..].iter().map(|(_, v)| *v));
+  right_out.extend(right_sorted[ri..].iter().map(|(_, v)| *v));
+
+  Ok(Diff {
+    left: left_out,
+    both: both_out,
+    right: right_out,
+    ignored,
+  })
+}
+
+/// Like [`diff`], but takes a comparator instead of requiring `K: Ord`.
+///
+/// Useful when the extracted key can't or shouldn't implement `Ord` (floats,
+/// case-insensitive strings, locale-aware collation, ...). Keys are sorted with
+/// `cmp` on each side, then matched with a merge-join over the two sorted
+/// sequences, which keeps the same O(n log n) complexity as [`diff`]. As with
+/// [`diff`], a repeated key is resolved last-occurrence-wins, with earlier
+/// occurrences routed to `ignored`.
+pub fn diff_by<'a, 'k, K, Left, Right, F>(
+  left: &'a [Left],
+  right: &'a [Right],
+  cmp: F,
+) -> Diff<'a, Left, Right>
+where
+  'a: 'k,
+  F: Fn(&K, &K) -> Ordering,
+  Left: HasItemKey<'k, K>,
+  Right: HasItemKey<'k, K>,
+{
+  let left_keyed: Vec<(K, &Left)> = left.iter().map(|item| (item.get_item_key(), item)).collect();
+  let right_keyed: Vec<(K, &Right)> = right.iter().map(|item| (item.get_item_key(), item)).collect();
+
+  let (left_sorted, left_ignored) = sort_dedup_by(left_keyed, &cmp);
+  let (right_sorted, right_ignored) = sort_dedup_by(right_keyed, &cmp);
+
+  let mut diff = Diff {
+    left: vec![],
+    both: vec![],
+    right: vec![],
+    ignored: left_ignored
+      .into_iter()
+      .map(DiffIgnored::Left)
+      .chain(right_ignored.into_iter().map(DiffIgnored::Right))
+      .collect(),
+  };
+
+  let mut li = 0;
+  let mut ri = 0;
+
+  while li < left_sorted.len() && ri < right_sorted.len() {
+    match cmp(&left_sorted[li].0, &right_sorted[ri].0) {
+      Ordering::Less => {
+        diff.left.push(left_sorted[li].1);
+        li += 1;
+      }
+      Ordering::Greater => {
+        diff.right.push(right_sorted[ri].1);
+        ri += 1;
+      }
+      Ordering::Equal => {
+        diff.both.push((left_sorted[li].1, right_sorted[ri].1));
+        li += 1;
+        ri += 1;
+      }
+    }
+  }
+
+  diff.left.extend(left_sorted[li..].iter().map(|(_, v)| *v));
+  diff.right.extend(right_sorted[ri..].iter().map(|(_, v)| *v));
+
+  diff
+}
+
+/// Sorted, deduplicated keyed items, paired with the items displaced by a
+/// repeated key, as returned by [`sort_dedup_by`]/[`try_sort_dedup_by`].
+type SortDeduped<'a, K, T> = (Vec<(K, &'a T)>, Vec<&'a T>);
+
+/// Sorts `keyed` by `cmp` and collapses runs of equal keys, keeping the last
+/// item of each run and routing the rest to the returned ignored list.
+fn sort_dedup_by<K, T>(keyed: Vec<(K, &T)>, cmp: impl Fn(&K, &K) -> Ordering) -> SortDeduped<'_, K, T> {
+  try_sort_dedup_by(keyed, cmp).expect("allocation failed while sorting/deduping")
+}
+
+/// Like [`sort_dedup_by`], but uses fallible allocation for the `Vec`s it
+/// builds; the stable sort's scratch buffer is allocated infallibly.
+fn try_sort_dedup_by<K, T>(
+  mut keyed: Vec<(K, &T)>,
+  cmp: impl Fn(&K, &K) -> Ordering,
+) -> Result<SortDeduped<'_, K, T>, TryReserveError> {
+  keyed.sort_by(|a, b| cmp(&a.0, &b.0));
+
+  let mut deduped: Vec<(K, &T)> = Vec::new();
+  deduped.try_reserve(keyed.len())?;
+  let mut ignored = Vec::new();
+
+  for (k, item) in keyed {
+    match deduped.last_mut() {
+      Some((last_k, last_item)) if cmp(last_k, &k) == Ordering::Equal => {
+        ignored.try_reserve(1)?;
+        ignored.push(std::mem::replace(last_item, item));
+      }
+      _ => deduped.push((k, item)),
     }
   }
 
+  Ok((deduped, ignored))
+}
+
+/// Like [`diff`], but `left` and `both` are emitted in the order items appear
+/// in `left`, and `right` in the order items appear in `right`, instead of
+/// sorted by key.
+///
+/// Useful for diffing ordered config entries, UI lists, or serialized
+/// sequences, where callers care about the original ordering rather than key
+/// order. As with [`diff`], a repeated key is resolved last-occurrence-wins,
+/// with earlier occurrences routed to `ignored` in input order; the surviving
+/// occurrence keeps the position of its key's first appearance.
+pub fn diff_ordered<'a, 'k, K, Left, Right>(left: &'a [Left], right: &'a [Right]) -> Diff<'a, Left, Right>
+where
+  'a: 'k,
+  K: Eq + Hash + Clone,
+  Left: HasItemKey<'k, K>,
+  Right: HasItemKey<'k, K>,
+{
+  let mut ignored: Vec<DiffIgnored<&'a Left, &'a Right>> = vec![];
+
+  let left_ordered = OrderedIndex::build(left, &mut ignored, DiffIgnored::Left);
+  let right_ordered = OrderedIndex::build(right, &mut ignored, DiffIgnored::Right);
+
   let mut diff = Diff {
     left: vec![],
     both: vec![],
@@ -62,22 +280,70 @@ where
     ignored,
   };
 
-  for (k, v) in &left_map {
-    match right_map.remove(k) {
-      None => {
-        diff.left.push(*v);
+  let mut matched_right: HashSet<usize> = HashSet::new();
+
+  for (key, l) in &left_ordered.entries {
+    match right_ordered.index.get(key) {
+      Some(&ri) => {
+        diff.both.push((*l, right_ordered.entries[ri].1));
+        matched_right.insert(ri);
       }
-      Some(right) => diff.both.push((*v, right)),
+      None => diff.left.push(*l),
     }
   }
 
-  if !right_map.is_empty() {
-    diff.right = right_map.into_iter().map(|(_, v)| v).collect();
+  for (ri, (_, r)) in right_ordered.entries.iter().enumerate() {
+    if !matched_right.contains(&ri) {
+      diff.right.push(*r);
+    }
   }
 
   diff
 }
 
+/// Keys `items` in input order, keeping a `Vec` of first-seen entries
+/// alongside a `HashMap` from key to position in that `Vec`. A repeated key
+/// overwrites the value at its original position and routes the previous
+/// value to `ignored` via `wrap`.
+struct OrderedIndex<'a, K, T> {
+  entries: Vec<(K, &'a T)>,
+  index: HashMap<K, usize>,
+}
+
+impl<'a, K, T> OrderedIndex<'a, K, T>
+where
+  K: Eq + Hash + Clone,
+{
+  fn build<'k, Ignored>(
+    items: &'a [T],
+    ignored: &mut Vec<Ignored>,
+    wrap: impl Fn(&'a T) -> Ignored,
+  ) -> Self
+  where
+    'a: 'k,
+    T: HasItemKey<'k, K>,
+  {
+    let mut entries: Vec<(K, &'a T)> = vec![];
+    let mut index: HashMap<K, usize> = HashMap::new();
+
+    for item in items {
+      let key = item.get_item_key();
+      match index.get(&key) {
+        Some(&pos) => {
+          let old = std::mem::replace(&mut entries[pos].1, item);
+          ignored.push(wrap(old));
+        }
+        None => {
+          index.insert(key.clone(), entries.len());
+          entries.push((key, item));
+        }
+      }
+    }
+
+    OrderedIndex { entries, index }
+  }
+}
+
 pub fn with_key<'a, T, F, K>(list: &'a [T], f: F) -> Vec<WithKey<&'a T, K>>
 where
   F: Fn(&'a T) -> K,
@@ -174,6 +440,188 @@ fn test_diff() {
   );
 }
 
+#[test]
+fn test_try_diff() {
+  #[derive(Debug, PartialEq)]
+  struct V1(String);
+  impl<'s> HasItemKey<'s, &'s str> for V1 {
+    fn get_item_key(&'s self) -> &'s str {
+      self.0.as_ref()
+    }
+  }
+
+  #[derive(Debug, PartialEq)]
+  struct V2(&'static str);
+  impl<'s> HasItemKey<'s, &'s str> for V2 {
+    fn get_item_key(&'s self) -> &'s str {
+      self.0
+    }
+  }
+
+  let l1: Vec<V1> = ["0", "a", "a", "a", "b", "b", "c"]
+    .iter()
+    .map(|v| V1(v.to_string()))
+    .collect();
+  let l2: Vec<V2> = ["a", "a", "a", "b", "b", "c", "d"]
+    .iter()
+    .map(|v| V2(v))
+    .collect();
+
+  let res = try_diff(&l1, &l2).expect("diffing should not fail");
+
+  assert_eq!(res.left, vec![&V1("0".to_string())]);
+  assert_eq!(
+    res.both,
+    vec![
+      (&V1("a".to_string()), &V2("a")),
+      (&V1("b".to_string()), &V2("b")),
+      (&V1("c".to_string()), &V2("c"))
+    ]
+  );
+  assert_eq!(res.right, vec![&V2("d")]);
+  assert_eq!(
+    res.ignored,
+    vec![
+      DiffIgnored::Left(&V1("a".to_string())),
+      DiffIgnored::Left(&V1("a".to_string())),
+      DiffIgnored::Left(&V1("b".to_string())),
+      DiffIgnored::Right(&V2("a")),
+      DiffIgnored::Right(&V2("a")),
+      DiffIgnored::Right(&V2("b")),
+    ]
+  );
+}
+
+#[test]
+fn test_diff_with_changes() {
+  #[derive(Debug, PartialEq)]
+  struct V1(&'static str, i32);
+  impl<'s> HasItemKey<'s, &'s str> for V1 {
+    fn get_item_key(&'s self) -> &'s str {
+      self.0
+    }
+  }
+
+  #[derive(Debug, PartialEq)]
+  struct V2(&'static str, i32);
+  impl<'s> HasItemKey<'s, &'s str> for V2 {
+    fn get_item_key(&'s self) -> &'s str {
+      self.0
+    }
+  }
+
+  let l1 = vec![V1("a", 1), V1("b", 2)];
+  let l2 = vec![V2("a", 1), V2("b", 3)];
+
+  let res = diff(&l1, &l2);
+  let changes = res.with_changes(|l: &V1, r: &V2| l.1 == r.1);
+
+  assert_eq!(changes.unchanged, vec![(&V1("a", 1), &V2("a", 1))]);
+  assert_eq!(changes.changed, vec![(&V1("b", 2), &V2("b", 3))]);
+}
+
+#[test]
+fn test_diff_by() {
+  #[derive(Debug, PartialEq)]
+  struct V1(String);
+  impl<'s> HasItemKey<'s, &'s str> for V1 {
+    fn get_item_key(&'s self) -> &'s str {
+      self.0.as_ref()
+    }
+  }
+
+  #[derive(Debug, PartialEq)]
+  struct V2(&'static str);
+  impl<'s> HasItemKey<'s, &'s str> for V2 {
+    fn get_item_key(&'s self) -> &'s str {
+      self.0
+    }
+  }
+
+  let l1: Vec<V1> = ["0", "a", "a", "a", "b", "b", "c"]
+    .iter()
+    .map(|v| V1(v.to_string()))
+    .collect();
+  let l2: Vec<V2> = ["a", "a", "a", "b", "b", "c", "d"]
+    .iter()
+    .map(|v| V2(v))
+    .collect();
+
+  let res = diff_by(&l1, &l2, |a: &&str, b: &&str| a.cmp(b));
+
+  assert_eq!(res.left, vec![&V1("0".to_string())]);
+  assert_eq!(
+    res.both,
+    vec![
+      (&V1("a".to_string()), &V2("a")),
+      (&V1("b".to_string()), &V2("b")),
+      (&V1("c".to_string()), &V2("c"))
+    ]
+  );
+  assert_eq!(res.right, vec![&V2("d")]);
+  assert_eq!(
+    res.ignored,
+    vec![
+      DiffIgnored::Left(&V1("a".to_string())),
+      DiffIgnored::Left(&V1("a".to_string())),
+      DiffIgnored::Left(&V1("b".to_string())),
+      DiffIgnored::Right(&V2("a")),
+      DiffIgnored::Right(&V2("a")),
+      DiffIgnored::Right(&V2("b")),
+    ]
+  );
+}
+
+#[test]
+fn test_diff_ordered() {
+  #[derive(Debug, PartialEq)]
+  struct V1(String);
+  impl<'s> HasItemKey<'s, &'s str> for V1 {
+    fn get_item_key(&'s self) -> &'s str {
+      self.0.as_ref()
+    }
+  }
+
+  #[derive(Debug, PartialEq)]
+  struct V2(&'static str);
+  impl<'s> HasItemKey<'s, &'s str> for V2 {
+    fn get_item_key(&'s self) -> &'s str {
+      self.0
+    }
+  }
+
+  let l1: Vec<V1> = ["c", "a", "a", "z", "b"]
+    .iter()
+    .map(|v| V1(v.to_string()))
+    .collect();
+  let l2: Vec<V2> = ["d", "b", "x", "x", "c"]
+    .iter()
+    .map(|v| V2(v))
+    .collect();
+
+  let res = diff_ordered(&l1, &l2);
+
+  assert_eq!(
+    res.left,
+    vec![&V1("a".to_string()), &V1("z".to_string())]
+  );
+  assert_eq!(
+    res.both,
+    vec![
+      (&V1("c".to_string()), &V2("c")),
+      (&V1("b".to_string()), &V2("b")),
+    ]
+  );
+  assert_eq!(res.right, vec![&V2("d"), &V2("x")]);
+  assert_eq!(
+    res.ignored,
+    vec![
+      DiffIgnored::Left(&V1("a".to_string())),
+      DiffIgnored::Right(&V2("x")),
+    ]
+  );
+}
+
 #[test]
 fn test_with_key() {
   let l1: Vec<String> = [1, 2, 3, 4].iter().map(|v| v.to_string()).collect();
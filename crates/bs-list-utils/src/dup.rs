@@ -0,0 +1,228 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, TryReserveError};
+use crate::HasItemKey;
+use std::ops::Deref;
+
+pub fn get_dups<'k, T, K>(list: &'k [T]) -> BTreeMap<K, usize>
+  where
+    T: HasItemKey<'k, K>,
+    K: Ord
+{
+  let mut map = BTreeMap::new();
+
+  for item in list {
+    let key = item.get_item_key();
+    let value = map.entry(key).or_insert(0);
+    *value += 1;
+  }
+
+  map.into_iter().filter(|(_, v)| {
+    *v > 1
+  }).collect()
+}
+
+/// Like [`get_dups`], but takes a comparator instead of requiring `K: Ord`.
+pub fn get_dups_by<'k, T, K, F>(list: &'k [T], cmp: F) -> Vec<(K, usize)>
+  where
+    T: HasItemKey<'k, K>,
+    F: Fn(&K, &K) -> Ordering,
+{
+  let mut keys: Vec<K> = list.iter().map(|item| item.get_item_key()).collect();
+  keys.sort_by(&cmp);
+
+  let mut counts: Vec<(K, usize)> = vec![];
+
+  for key in keys {
+    match counts.last_mut() {
+      Some((last_key, count)) if cmp(last_key, &key) == Ordering::Equal => *count += 1,
+      _ => counts.push((key, 1)),
+    }
+  }
+
+  counts.into_iter().filter(|(_, count)| *count > 1).collect()
+}
+
+#[derive(Debug)]
+pub struct ItemSet<T>(Vec<T>);
+
+impl<T> ItemSet<T> {
+  pub fn into_inner(self) -> Vec<T> {
+    self.0
+  }
+}
+
+impl<T> Deref for ItemSet<T> {
+  type Target = Vec<T>;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+/// Result of [`dedup`]/[`try_dedup`]: `removed` is sorted by key, since both
+/// require `K: Ord`, but is kept as a `Vec<(K, Vec<T>)>` rather than a
+/// `BTreeMap` so the fallible path has no infallible allocation to fall back on.
+#[derive(Debug)]
+pub struct Dedup<T, K> {
+  pub set: ItemSet<T>,
+  pub removed: Vec<(K, Vec<T>)>,
+}
+
+pub fn dedup<T, K>(list: Vec<T>) -> Dedup<T, K>
+where
+  K: Ord,
+  T: for<'k> HasItemKey<'k, K>
+{
+  try_dedup(list).expect("allocation failed while deduplicating")
+}
+
+/// Like [`dedup`], but uses fallible allocation for every `Vec` it builds, so
+/// oversized inputs are rejected with an error instead of aborting the process.
+/// The scratch buffer of the stable sort is allocated infallibly.
+///
+/// `BTreeMap` has no fallible-insert API in `std`, so this keys and groups
+/// items over plain `Vec`s we can `try_reserve` instead, the same
+/// sort-then-group approach used by [`dedup_by`].
+pub fn try_dedup<T, K>(list: Vec<T>) -> Result<Dedup<T, K>, TryReserveError>
+where
+  K: Ord,
+  T: for<'k> HasItemKey<'k, K>
+{
+  let mut keyed: Vec<(K, T)> = Vec::new();
+  keyed.try_reserve(list.len())?;
+  keyed.extend(list.into_iter().map(|item| (item.get_item_key(), item)));
+
+  keyed.sort_by(|a, b| a.0.cmp(&b.0));
+
+  let mut groups: Vec<(K, Vec<T>)> = Vec::new();
+  groups.try_reserve(keyed.len())?;
+
+  for (key, item) in keyed {
+    match groups.last_mut() {
+      Some((last_key, items)) if *last_key == key => {
+        items.try_reserve(1)?;
+        items.push(item);
+      }
+      _ => {
+        let mut items = Vec::new();
+        items.try_reserve(1)?;
+        items.push(item);
+        groups.push((key, items));
+      }
+    }
+  }
+
+  let mut res = Dedup {
+    set: ItemSet(vec![]),
+    removed: vec![],
+  };
+  res.set.0.try_reserve(groups.len())?;
+  res.removed.try_reserve(groups.len())?;
+
+  for (key, mut items) in groups {
+    res.set.0.push(items.pop().unwrap());
+    if !items.is_empty() {
+      res.removed.push((key, items));
+    }
+  }
+
+  Ok(res)
+}
+
+/// Result of [`dedup_by`]: like [`Dedup`], but `removed` is sorted by the
+/// caller's comparator instead of `K: Ord`; within each key, removed items keep
+/// their input order.
+#[derive(Debug)]
+pub struct DedupBy<T, K> {
+  pub set: ItemSet<T>,
+  pub removed: Vec<(K, Vec<T>)>,
+}
+
+/// Like [`dedup`], but takes a comparator instead of requiring `K: Ord`.
+pub fn dedup_by<T, K, F>(list: Vec<T>, cmp: F) -> DedupBy<T, K>
+where
+  F: Fn(&K, &K) -> Ordering,
+  T: for<'k> HasItemKey<'k, K>,
+{
+  let mut keyed: Vec<(K, T)> = list.into_iter().map(|item| (item.get_item_key(), item)).collect();
+  keyed.sort_by(|a, b| cmp(&a.0, &b.0));
+
+  let mut groups: Vec<(K, Vec<T>)> = vec![];
+
+  for (key, item) in keyed {
+    match groups.last_mut() {
+      Some((last_key, items)) if cmp(last_key, &key) == Ordering::Equal => items.push(item),
+      _ => groups.push((key, vec![item])),
+    }
+  }
+
+  let mut res = DedupBy {
+    set: ItemSet(vec![]),
+    removed: vec![],
+  };
+
+  for (key, mut items) in groups {
+    res.set.0.push(items.pop().unwrap());
+    if !items.is_empty() {
+      res.removed.push((key, items));
+    }
+  }
+
+  res
+}
+
+#[test]
+fn test_get_dups() {
+  let list = &[1,1,1,1,2,2,2,3,3,4,5,6];
+  let map = get_dups(list);
+  assert_eq!(map, {
+    vec![
+      (1, 4),
+      (2, 3),
+      (3, 2),
+    ].into_iter().collect()
+  });
+}
+
+#[test]
+fn test_dedup() {
+  let list = &[1,1,1,1,2,2,2,3,3,4,5,6];
+  let dedup = dedup(list.to_vec());
+  assert_eq!(&dedup.set as &[i32], &[1,2,3,4,5,6]);
+  assert_eq!(dedup.removed, vec![
+    (1, vec![1, 1, 1]),
+    (2, vec![2, 2]),
+    (3, vec![3]),
+  ]);
+}
+
+#[test]
+fn test_try_dedup() {
+  let list = &[1,1,1,1,2,2,2,3,3,4,5,6];
+  let dedup = try_dedup(list.to_vec()).expect("deduplicating should not fail");
+  assert_eq!(&dedup.set as &[i32], &[1,2,3,4,5,6]);
+  assert_eq!(dedup.removed, vec![
+    (1, vec![1, 1, 1]),
+    (2, vec![2, 2]),
+    (3, vec![3]),
+  ]);
+}
+
+#[test]
+fn test_get_dups_by() {
+  let list = &[1,1,1,1,2,2,2,3,3,4,5,6];
+  let dups = get_dups_by(list, |a, b| a.cmp(b));
+  assert_eq!(dups, vec![(1, 4), (2, 3), (3, 2)]);
+}
+
+#[test]
+fn test_dedup_by() {
+  let list = &[1,1,1,1,2,2,2,3,3,4,5,6];
+  let dedup = dedup_by(list.to_vec(), |a, b| a.cmp(b));
+  assert_eq!(&dedup.set as &[i32], &[1,2,3,4,5,6]);
+  assert_eq!(dedup.removed, vec![
+    (1, vec![1, 1, 1]),
+    (2, vec![2, 2]),
+    (3, vec![3]),
+  ]);
+}
\ No newline at end of file
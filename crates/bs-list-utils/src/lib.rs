@@ -1,5 +1,6 @@
 pub mod diff;
 pub mod dup;
+pub mod merge;
 
 pub trait HasItemKey<'s, K> {
   fn get_item_key(&'s self) -> K;
@@ -0,0 +1,211 @@
+use crate::HasItemKey;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How a genuine conflict (both sides changed a shared key differently) is
+/// resolved by [`merge3`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+  /// Withhold conflicting keys from `merged` and report them in `conflicts`.
+  Conflict,
+  /// Resolve conflicting keys to the right-hand value, without reporting a conflict.
+  RightBiased,
+}
+
+/// Which side an entry came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+  Left,
+  Right,
+}
+
+#[derive(Debug)]
+pub enum Merged<'a, T> {
+  /// Unchanged from `base` on both sides.
+  Unchanged(&'a T),
+  /// New relative to `base`, present on exactly one side.
+  Added(Side, &'a T),
+  /// Present in `base`, dropped from exactly one side. The deletion wins
+  /// even if the other side also changed the value.
+  Removed(Side, &'a T),
+  /// Changed relative to `base` on exactly one side; that side's value wins.
+  Changed(Side, &'a T),
+  /// Changed relative to `base` on both sides, to the same resulting value.
+  SameChangeOnBoth(&'a T, &'a T),
+  /// Changed differently on both sides, resolved by `MergeStrategy::RightBiased`.
+  ResolvedRightBiased(&'a T, &'a T),
+}
+
+/// A key that changed differently on both sides relative to `base`, reported
+/// under `MergeStrategy::Conflict`.
+#[derive(Debug)]
+pub struct Conflict<'a, T> {
+  pub left: &'a T,
+  pub right: &'a T,
+}
+
+#[derive(Debug)]
+pub struct Merge3<'a, T> {
+  pub merged: Vec<Merged<'a, T>>,
+  pub conflicts: Vec<Conflict<'a, T>>,
+}
+
+/// Three-way merge of `base`, `left`, and `right`, keyed by `HasItemKey`.
+///
+/// Each key is classified relative to `base` as unchanged, added on one side,
+/// removed from one side, changed on one side, changed identically on both
+/// sides, or a genuine conflict (changed differently on both sides). `eq`
+/// decides whether two values are the same; `strategy` decides how conflicts
+/// are resolved. A key repeated within one input is resolved last-occurrence-wins,
+/// matching [`crate::diff::diff`].
+pub fn merge3<'a, 'k, K, T, F>(
+  base: &'a [T],
+  left: &'a [T],
+  right: &'a [T],
+  strategy: MergeStrategy,
+  eq: F,
+) -> Merge3<'a, T>
+where
+  'a: 'k,
+  K: Ord,
+  T: HasItemKey<'k, K>,
+  F: Fn(&T, &T) -> bool,
+{
+  let base_map: BTreeMap<K, &T> = base.iter().map(|item| (item.get_item_key(), item)).collect();
+  let left_map: BTreeMap<K, &T> = left.iter().map(|item| (item.get_item_key(), item)).collect();
+  let right_map: BTreeMap<K, &T> = right.iter().map(|item| (item.get_item_key(), item)).collect();
+
+  let mut keys: BTreeSet<&K> = BTreeSet::new();
+  keys.extend(base_map.keys());
+  keys.extend(left_map.keys());
+  keys.extend(right_map.keys());
+
+  let mut result = Merge3 {
+    merged: vec![],
+    conflicts: vec![],
+  };
+
+  for key in keys {
+    let b = base_map.get(key).copied();
+    let l = left_map.get(key).copied();
+    let r = right_map.get(key).copied();
+
+    match (b, l, r) {
+      (Some(b), Some(l), Some(r)) => {
+        let left_changed = !eq(b, l);
+        let right_changed = !eq(b, r);
+        match (left_changed, right_changed) {
+          (false, false) => result.merged.push(Merged::Unchanged(b)),
+          (true, false) => result.merged.push(Merged::Changed(Side::Left, l)),
+          (false, true) => result.merged.push(Merged::Changed(Side::Right, r)),
+          (true, true) => resolve_conflict(&mut result, strategy, eq(l, r), l, r),
+        }
+      }
+      (Some(b), None, Some(_)) => result.merged.push(Merged::Removed(Side::Left, b)),
+      (Some(b), Some(_), None) => result.merged.push(Merged::Removed(Side::Right, b)),
+      (Some(_), None, None) => {}
+      (None, Some(l), Some(r)) => resolve_conflict(&mut result, strategy, eq(l, r), l, r),
+      (None, Some(l), None) => result.merged.push(Merged::Added(Side::Left, l)),
+      (None, None, Some(r)) => result.merged.push(Merged::Added(Side::Right, r)),
+      (None, None, None) => unreachable!(),
+    }
+  }
+
+  result
+}
+
+fn resolve_conflict<'a, T>(
+  result: &mut Merge3<'a, T>,
+  strategy: MergeStrategy,
+  same: bool,
+  left: &'a T,
+  right: &'a T,
+) {
+  if same {
+    result.merged.push(Merged::SameChangeOnBoth(left, right));
+    return;
+  }
+
+  match strategy {
+    MergeStrategy::Conflict => result.conflicts.push(Conflict { left, right }),
+    MergeStrategy::RightBiased => result.merged.push(Merged::ResolvedRightBiased(left, right)),
+  }
+}
+
+#[test]
+fn test_merge3() {
+  #[derive(Debug, PartialEq)]
+  struct Record {
+    key: &'static str,
+    value: i32,
+  }
+  impl<'s> HasItemKey<'s, &'s str> for Record {
+    fn get_item_key(&'s self) -> &'s str {
+      self.key
+    }
+  }
+
+  let eq = |a: &Record, b: &Record| a.value == b.value;
+
+  let base = vec![
+    Record { key: "unchanged", value: 1 },
+    Record { key: "changed_left", value: 1 },
+    Record { key: "changed_right", value: 1 },
+    Record { key: "conflict", value: 1 },
+    Record { key: "removed_left", value: 1 },
+    Record { key: "removed_right", value: 1 },
+    Record { key: "removed_left_changed_right", value: 1 },
+    Record { key: "removed_right_changed_left", value: 1 },
+    Record { key: "duplicated_key", value: 1 },
+  ];
+  let left = vec![
+    Record { key: "unchanged", value: 1 },
+    Record { key: "changed_left", value: 2 },
+    Record { key: "changed_right", value: 1 },
+    Record { key: "conflict", value: 2 },
+    Record { key: "removed_right", value: 1 },
+    Record { key: "removed_right_changed_left", value: 2 },
+    Record { key: "added_left", value: 1 },
+    Record { key: "added_both_same", value: 1 },
+    Record { key: "added_both_conflict", value: 1 },
+    Record { key: "duplicated_key", value: 2 },
+    Record { key: "duplicated_key", value: 3 },
+  ];
+  let right = vec![
+    Record { key: "unchanged", value: 1 },
+    Record { key: "changed_left", value: 1 },
+    Record { key: "changed_right", value: 2 },
+    Record { key: "conflict", value: 3 },
+    Record { key: "removed_left", value: 1 },
+    Record { key: "removed_left_changed_right", value: 2 },
+    Record { key: "added_right", value: 1 },
+    Record { key: "added_both_same", value: 1 },
+    Record { key: "added_both_conflict", value: 2 },
+    Record { key: "duplicated_key", value: 1 },
+  ];
+
+  let res = merge3(&base, &left, &right, MergeStrategy::Conflict, eq);
+
+  assert_eq!(res.conflicts.len(), 2);
+  assert!(res.conflicts.iter().any(|c| c.left.key == "conflict" && c.right.key == "conflict"));
+  assert!(res.conflicts.iter().any(|c| c.left.key == "added_both_conflict" && c.right.key == "added_both_conflict"));
+
+  assert!(res.merged.iter().any(|m| matches!(m, Merged::Unchanged(r) if r.key == "unchanged")));
+  assert!(res.merged.iter().any(|m| matches!(m, Merged::Changed(Side::Left, r) if r.key == "changed_left")));
+  assert!(res.merged.iter().any(|m| matches!(m, Merged::Changed(Side::Right, r) if r.key == "changed_right")));
+  assert!(res.merged.iter().any(|m| matches!(m, Merged::Removed(Side::Left, r) if r.key == "removed_left")));
+  assert!(res.merged.iter().any(|m| matches!(m, Merged::Removed(Side::Right, r) if r.key == "removed_right")));
+  assert!(res.merged.iter().any(|m| matches!(m, Merged::Removed(Side::Left, r) if r.key == "removed_left_changed_right")));
+  assert!(res.merged.iter().any(|m| matches!(m, Merged::Removed(Side::Right, r) if r.key == "removed_right_changed_left")));
+  assert!(res.merged.iter().any(|m| matches!(m, Merged::Added(Side::Left, r) if r.key == "added_left")));
+  assert!(res.merged.iter().any(|m| matches!(m, Merged::Added(Side::Right, r) if r.key == "added_right")));
+  assert!(res.merged.iter().any(|m| matches!(m, Merged::SameChangeOnBoth(l, r) if l.key == "added_both_same" && r.key == "added_both_same")));
+  // A key repeated within one input resolves last-occurrence-wins, matching diff/diff_by/diff_ordered.
+  assert!(res.merged.iter().any(|m| matches!(m, Merged::Changed(Side::Left, r) if r.key == "duplicated_key" && r.value == 3)));
+
+  let res_right_biased = merge3(&base, &left, &right, MergeStrategy::RightBiased, eq);
+  assert!(res_right_biased.conflicts.is_empty());
+  assert!(res_right_biased.merged.iter().any(|m| matches!(
+    m,
+    Merged::ResolvedRightBiased(l, r) if l.key == "conflict" && r.key == "conflict"
+  )));
+}